@@ -0,0 +1,88 @@
+//! Bakes each bundled language JSON model into the compact binary format that
+//! the language constructors load with `include_bytes!`. Emitting the `.bin`
+//! blobs here - into `OUT_DIR`, before the library itself is compiled - keeps
+//! the win of the binary format (no JSON tokenization at load time) without
+//! committing generated artifacts or creating a build cycle against the
+//! library's own `write_binary`. The layout written here mirrors `from_binary`
+//! exactly: a varint count then length-prefixed UTF-8 strings for abbreviations
+//! and sentence starters, `left`/`right` pairs for collocations, and a
+//! `token`/`u8` stream for the orthographic context.
+
+extern crate "rustc-serialize" as rustc_serialize;
+
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use rustc_serialize::json::Json;
+
+static LANGUAGES: &'static [&'static str] = &[
+  "czech", "danish", "dutch", "english", "estonian", "finnish", "french",
+  "german", "greek", "italian", "norwegian", "polish", "portuguese", "slovene",
+  "spanish", "swedish", "turkish"
+];
+
+fn write_varint<W: Write>(w: &mut W, mut n: usize) {
+  loop {
+    let mut byte = (n & 0x7f) as u8;
+    n >>= 7;
+
+    if n != 0 { byte |= 0x80; }
+
+    w.write_all(&[byte]).unwrap();
+
+    if n == 0 { break; }
+  }
+}
+
+fn write_str<W: Write>(w: &mut W, s: &str) {
+  write_varint(w, s.len());
+  w.write_all(s.as_bytes()).unwrap();
+}
+
+fn main() {
+  let out_dir = env::var("OUT_DIR").unwrap();
+
+  for lang in LANGUAGES.iter() {
+    let json_path = format!("src/trainer/data/{}.json", lang);
+
+    let mut src = String::new();
+    File::open(&json_path).unwrap().read_to_string(&mut src).unwrap();
+
+    let json = Json::from_str(src.as_slice()).unwrap();
+    let obj = json.as_object().unwrap();
+
+    let bin_path = Path::new(&out_dir).join(format!("{}.bin", lang));
+    let mut out = File::create(&bin_path).unwrap();
+
+    let abbrevs = obj.get("abbrev_types").unwrap().as_array().unwrap();
+    write_varint(&mut out, abbrevs.len());
+    for a in abbrevs.iter() {
+      write_str(&mut out, a.as_string().unwrap());
+    }
+
+    let starters = obj.get("sentence_starters").unwrap().as_array().unwrap();
+    write_varint(&mut out, starters.len());
+    for s in starters.iter() {
+      write_str(&mut out, s.as_string().unwrap());
+    }
+
+    let collocations = obj.get("collocations").unwrap().as_array().unwrap();
+    write_varint(&mut out, collocations.len());
+    for c in collocations.iter() {
+      let pair = c.as_array().unwrap();
+      write_str(&mut out, pair[0].as_string().unwrap());
+      write_str(&mut out, pair[1].as_string().unwrap());
+    }
+
+    let ortho = obj.get("ortho_context").unwrap().as_object().unwrap();
+    write_varint(&mut out, ortho.len());
+    for (tok, v) in ortho.iter() {
+      write_str(&mut out, tok.as_slice());
+      out.write_all(&[v.as_u64().unwrap() as u8]).unwrap();
+    }
+
+    println!("cargo:rerun-if-changed={}", json_path);
+  }
+}
@@ -0,0 +1,52 @@
+//! Offline converter that turns the source language JSON models into the
+//! compact binary format read by `TrainingData::from_binary`:
+//!
+//! ```sh
+//! cargo run --example build_binary
+//! ```
+//!
+//! Each `<lang>.json` in `src/trainer/data` is parsed once with the existing
+//! `FromStr` impl and re-emitted alongside it as `<lang>.bin`, using the
+//! library's own `write_binary`. The language constructors
+//! (`TrainingData::{english, german, ...}`) do not read these files: they
+//! `include_bytes!` the blobs that `build.rs` writes into `OUT_DIR` on every
+//! build.
+
+extern crate punkt;
+
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+
+use punkt::trainer::TrainingData;
+
+static LANGUAGES: &'static [&'static str] = &[
+  "czech", "danish", "dutch", "english", "estonian", "finnish", "french",
+  "german", "greek", "italian", "norwegian", "polish", "portuguese", "slovene",
+  "spanish", "swedish", "turkish"
+];
+
+fn main() {
+  for lang in LANGUAGES.iter() {
+    let json_path = format!("src/trainer/data/{}.json", lang);
+    let bin_path = format!("src/trainer/data/{}.bin", lang);
+
+    let mut src = String::new();
+    File::open(&json_path)
+      .ok()
+      .and_then(|mut f| f.read_to_string(&mut src).ok())
+      .expect(format!("failed to read {}", json_path).as_slice());
+
+    let data: TrainingData = FromStr::from_str(src.as_slice())
+      .expect(format!("failed to parse {}", json_path).as_slice());
+
+    let mut out = File::create(&bin_path)
+      .ok()
+      .expect(format!("failed to create {}", bin_path).as_slice());
+
+    data.write_binary(&mut out)
+      .expect(format!("failed to write {}", bin_path).as_slice());
+
+    println!("{} -> {}", json_path, bin_path);
+  }
+}
@@ -1,7 +1,10 @@
+use std::fmt;
+use std::io::{BufRead, Read, Write};
 use std::hash::Hash;
 use std::str::FromStr;
 use std::default::Default;
 use std::borrow::BorrowFrom;
+use std::collections::BTreeMap;
 use std::collections::{HashSet, HashMap};
 use std::collections::hash_set::Iter as HashSetIter;
 use std::collections::hash_map::Iter as HashMapIter;
@@ -32,35 +35,127 @@ pub struct TrainingData {
   orthographic_context: HashMap<String, OrthographicContext, XXState>
 }
 
-// Macro for generating functions to load precompiled data.
+// Macro for generating functions to load precompiled data. The source of truth
+// stays the bundled JSON files, but `build.rs` transcodes each one into the
+// compact binary format (see `write_binary`/`from_binary`) under `OUT_DIR`
+// before the library is compiled, and the constructors `include_bytes!` that
+// blob. Loading therefore materializes the xxhash-backed maps directly, without
+// tokenizing JSON on every call, while keeping the crate buildable from a clean
+// checkout (no generated artifact committed, no cycle against `write_binary`).
 macro_rules! preloaded_data(
-  ($lang:ident, $file:expr) => (
+  ($lang:ident) => (
     impl TrainingData {
       #[inline]
       pub fn $lang() -> TrainingData {
-        FromStr::from_str(include_str!($file)).unwrap()
+        TrainingData::from_binary(&mut include_bytes!(
+          concat!(env!("OUT_DIR"), "/", stringify!($lang), ".bin")).as_slice()).unwrap()
       }
     }
   )
 );
 
-preloaded_data!(czech, "data/czech.json");
-preloaded_data!(danish, "data/danish.json");
-preloaded_data!(dutch, "data/dutch.json");
-preloaded_data!(english, "data/english.json");
-preloaded_data!(estonian, "data/estonian.json");
-preloaded_data!(finnish, "data/finnish.json");
-preloaded_data!(french, "data/french.json");
-preloaded_data!(german, "data/german.json");
-preloaded_data!(greek, "data/greek.json");
-preloaded_data!(italian, "data/italian.json");
-preloaded_data!(norwegian, "data/norwegian.json");
-preloaded_data!(polish, "data/polish.json");
-preloaded_data!(portuguese, "data/portuguese.json");
-preloaded_data!(slovene, "data/slovene.json");
-preloaded_data!(spanish, "data/spanish.json");
-preloaded_data!(swedish, "data/swedish.json");
-preloaded_data!(turkish, "data/turkish.json");
+// Propagates a None out of the enclosing function, unwrapping a Some. The
+// binary reader/writer thread `Option` through every step, so this keeps the
+// short-circuiting terse.
+macro_rules! try_opt(
+  ($e:expr) => (match $e { Some(x) => x, None => return None });
+);
+
+// Writes an unsigned integer as a LEB128 style varint. Used for both counts
+// and string lengths in the binary format.
+#[inline]
+fn write_varint<W: Write>(w: &mut W, mut n: usize) -> Option<()> {
+  loop {
+    let mut byte = (n & 0x7f) as u8;
+    n >>= 7;
+
+    if n != 0 { byte |= 0x80; }
+
+    if w.write_all(&[byte]).is_err() { return None; }
+
+    if n == 0 { break; }
+  }
+
+  Some(())
+}
+
+// Fills `buf` entirely, looping over `read` because a single call may return
+// fewer bytes than requested for any reader other than an in-memory slice.
+// Returns false on EOF or error before the buffer is full.
+#[inline]
+fn read_full<R: Read>(r: &mut R, buf: &mut [u8]) -> bool {
+  let mut filled = 0;
+
+  while filled < buf.len() {
+    match r.read(buf.slice_from_mut(filled)) {
+      Ok(0) | Err(_) => return false,
+      Ok(n)          => filled += n
+    }
+  }
+
+  true
+}
+
+// Reads a varint written by `write_varint`. Returns None on a truncated or
+// overlong encoding.
+#[inline]
+fn read_varint<R: Read>(r: &mut R) -> Option<usize> {
+  let mut n: usize = 0;
+  let mut shift = 0us;
+
+  loop {
+    let mut buf = [0u8; 1];
+
+    if !read_full(r, &mut buf) { return None; }
+
+    n |= ((buf[0] & 0x7f) as usize) << shift;
+
+    if buf[0] & 0x80 == 0 { break; }
+
+    shift += 7;
+
+    if shift >= 64 { return None; }
+  }
+
+  Some(n)
+}
+
+// Writes a length-prefixed UTF-8 string.
+#[inline]
+fn write_str<W: Write>(w: &mut W, s: &str) -> Option<()> {
+  write_varint(w, s.len()).and_then(|_| {
+    if w.write_all(s.as_bytes()).is_err() { None } else { Some(()) }
+  })
+}
+
+// Reads a length-prefixed UTF-8 string.
+#[inline]
+fn read_str<R: Read>(r: &mut R) -> Option<String> {
+  let len = match read_varint(r) { Some(l) => l, None => return None };
+  let mut buf = Vec::from_elem(len, 0u8);
+
+  if !read_full(r, buf.as_mut_slice()) { return None; }
+
+  String::from_utf8(buf).ok()
+}
+
+preloaded_data!(czech);
+preloaded_data!(danish);
+preloaded_data!(dutch);
+preloaded_data!(english);
+preloaded_data!(estonian);
+preloaded_data!(finnish);
+preloaded_data!(french);
+preloaded_data!(german);
+preloaded_data!(greek);
+preloaded_data!(italian);
+preloaded_data!(norwegian);
+preloaded_data!(polish);
+preloaded_data!(portuguese);
+preloaded_data!(slovene);
+preloaded_data!(spanish);
+preloaded_data!(swedish);
+preloaded_data!(turkish);
 
 impl TrainingData {
   /// Returns the inner representation of compiled abbreviation types.
@@ -318,6 +413,227 @@ impl TrainingData {
   pub fn orthographic_context_iter(&self) -> HashMapIter<String, u8> {
     self.orthographic_context().iter()
   }
+
+  /// Encodes the compiled data into a `Json` object mirroring the layout that
+  /// `from_str` expects, so that a `TrainingData` built incrementally by a
+  /// `PunktTrainer` can be written out and reloaded later. `abbrev_types` and
+  /// `sentence_starters` become string arrays, `collocations` becomes an array
+  /// of two element `[left, right]` string arrays, and `ortho_context` becomes
+  /// an object mapping each token to its orthographic context value.
+  pub fn to_json(&self) -> Json {
+    let mut obj = BTreeMap::new();
+
+    obj.insert(
+      "abbrev_types".to_string(),
+      Json::Array(self.abbrevs_iter().map(|s| Json::String(s.clone())).collect()));
+
+    obj.insert(
+      "sentence_starters".to_string(),
+      Json::Array(self.sentence_starters_iter().map(|s| Json::String(s.clone())).collect()));
+
+    obj.insert(
+      "collocations".to_string(),
+      Json::Array(self.collocations_iter().map(|(l, r)| {
+        Json::Array(vec![Json::String(l.to_string()), Json::String(r.to_string())])
+      }).collect()));
+
+    let mut ortho = BTreeMap::new();
+
+    for (tok, ctxt) in self.orthographic_context_iter() {
+      ortho.insert(tok.clone(), Json::U64(*ctxt as u64));
+    }
+
+    obj.insert("ortho_context".to_string(), Json::Object(ortho));
+
+    Json::Object(obj)
+  }
+
+  /// Writes the compiled data out in the compact binary format. Each of the
+  /// four collections is emitted as a varint count followed by its entries:
+  /// length-prefixed UTF-8 strings for abbreviations and sentence starters,
+  /// `left`/`right` string pairs for collocations, and a `token`/`u8` stream
+  /// for the orthographic context. This is the same layout that `build.rs`
+  /// bakes in for the preloaded languages and that `from_binary` reads back;
+  /// use it to persist a model you trained yourself into a blob that loads
+  /// without any JSON tokenization.
+  pub fn write_binary<W: Write>(&self, w: &mut W) -> Option<()> {
+    try_opt!(write_varint(w, self.abbrevs_len()));
+
+    for abbrev in self.abbrevs_iter() {
+      try_opt!(write_str(w, abbrev.as_slice()));
+    }
+
+    try_opt!(write_varint(w, self.sentence_starters_len()));
+
+    for starter in self.sentence_starters_iter() {
+      try_opt!(write_str(w, starter.as_slice()));
+    }
+
+    try_opt!(write_varint(w, self.collocations_len()));
+
+    for (l, r) in self.collocations_iter() {
+      try_opt!(write_str(w, l));
+      try_opt!(write_str(w, r));
+    }
+
+    try_opt!(write_varint(w, self.orthographic_context_len()));
+
+    for (tok, ctxt) in self.orthographic_context_iter() {
+      try_opt!(write_str(w, tok.as_slice()));
+
+      if w.write_all(&[*ctxt]).is_err() { return None; }
+    }
+
+    Some(())
+  }
+
+  /// Reads data back in from the compact binary format produced by
+  /// `write_binary`. Returns None if the stream is truncated or otherwise
+  /// malformed.
+  pub fn from_binary<R: Read>(r: &mut R) -> Option<TrainingData> {
+    let mut data: TrainingData = Default::default();
+
+    let abbrevs = try_opt!(read_varint(r));
+
+    for _ in range(0, abbrevs) {
+      data.mut_abbrev_types().insert(try_opt!(read_str(r)));
+    }
+
+    let starters = try_opt!(read_varint(r));
+
+    for _ in range(0, starters) {
+      data.mut_sentence_starters().insert(try_opt!(read_str(r)));
+    }
+
+    let collocations = try_opt!(read_varint(r));
+
+    for _ in range(0, collocations) {
+      let l = try_opt!(read_str(r));
+      let r = try_opt!(read_str(r));
+
+      data.insert_collocation(l.as_slice(), r.as_slice());
+    }
+
+    let orthos = try_opt!(read_varint(r));
+
+    for _ in range(0, orthos) {
+      let tok = try_opt!(read_str(r));
+      let mut buf = [0u8; 1];
+
+      if !read_full(r, &mut buf) { return None; }
+
+      data.mut_orthographic_context().insert(tok, buf[0]);
+    }
+
+    Some(data)
+  }
+
+  /// Layers the abbreviations, sentence starters, collocations and orthographic
+  /// contexts of `other` onto this object in place. The first three buckets are
+  /// unioned; orthographic context flags are OR-ed together for tokens that are
+  /// already present, since each context is a bitfield. This lets the shipped
+  /// language data stay immutable while user corrections trained into a second
+  /// object are folded on top.
+  pub fn extend(&mut self, other: &TrainingData) {
+    for abbrev in other.abbrevs_iter() {
+      self.insert_abbrev(abbrev.as_slice());
+    }
+
+    for starter in other.sentence_starters_iter() {
+      self.insert_sentence_starter(starter.as_slice());
+    }
+
+    for (l, r) in other.collocations_iter() {
+      self.insert_collocation(l, r);
+    }
+
+    for (tok, ctxt) in other.orthographic_context_iter() {
+      let combined = match self.get_orthographic_context(tok.as_slice()) {
+        Some(existing) => *existing | *ctxt,
+        None           => *ctxt
+      };
+
+      self.mut_orthographic_context().insert(tok.clone(), combined);
+    }
+  }
+
+  /// Consumes `other` and returns the union of the two objects, following the
+  /// same rules as `extend`.
+  #[inline]
+  pub fn merge(mut self, other: TrainingData) -> TrainingData {
+    self.extend(&other);
+    self
+  }
+
+  /// Reads a plain word list from `r`, one token per line, inserting each as an
+  /// abbreviation. Lines are trimmed and lowercased to match how keys are
+  /// stored; blank lines and `#` comments are skipped. A trailing Hunspell
+  /// affix suffix (everything from the first `/`) is stripped, so both a bare
+  /// `word` and a `word/flags` dictionary entry resolve to `word`.
+  pub fn load_abbrevs_from_reader<R: BufRead>(&mut self, r: &mut R) {
+    for line in r.lines() {
+      match line {
+        Ok(line) => match parse_wordlist_entry(line.as_slice()) {
+          Some(word) => { self.insert_abbrev(word.as_slice()); }
+          None       => ()
+        },
+        Err(_) => break
+      }
+    }
+  }
+
+  /// Reads a plain word list from `r`, one token per line, inserting each as a
+  /// sentence starter. Follows the same trimming, lowercasing, comment and
+  /// Hunspell affix handling as `load_abbrevs_from_reader`.
+  pub fn load_sentence_starters_from_reader<R: BufRead>(&mut self, r: &mut R) {
+    for line in r.lines() {
+      match line {
+        Ok(line) => match parse_wordlist_entry(line.as_slice()) {
+          Some(word) => { self.insert_sentence_starter(word.as_slice()); }
+          None       => ()
+        },
+        Err(_) => break
+      }
+    }
+  }
+}
+
+// Normalizes a single word list line into the key that gets stored, or None if
+// the line carries no token (blank or a `#` comment). Strips any Hunspell affix
+// suffix at the first `/`, drops a single trailing `.` so a curated `Dr.` style
+// entry matches the period-stripped abbrev keys, trims, and lowercases.
+fn parse_wordlist_entry(line: &str) -> Option<String> {
+  let trimmed = line.trim();
+
+  if trimmed.is_empty() || trimmed.starts_with("#") {
+    return None;
+  }
+
+  let word = match trimmed.find('/') {
+    Some(i) => trimmed.slice_to(i).trim(),
+    None    => trimmed
+  };
+
+  let word = if word.ends_with(".") {
+    word.slice_to(word.len() - 1)
+  } else {
+    word
+  };
+
+  if word.is_empty() {
+    None
+  } else {
+    Some(word.to_lowercase())
+  }
+}
+
+impl fmt::String for TrainingData {
+  /// Serializes the data into the same JSON representation that `FromStr` reads
+  /// back in, letting a trained model be persisted with `to_string`.
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.to_json())
+  }
 }
 
 impl Default for TrainingData {
@@ -454,3 +770,91 @@ fn test_data_load_from_json_test() {
   assert!(data.sentence_starters().len() > 0);
   assert!(data.collocations().len() > 0);
 }
+
+#[test]
+fn test_binary_round_trip_test() {
+  let mut data: TrainingData = Default::default();
+
+  data.insert_abbrev("dr");
+  data.insert_abbrev("inc");
+  data.insert_sentence_starter("the");
+  data.insert_collocation("new", "york");
+  data.insert_orthographic_context("the", 0x02);
+
+  let mut buf = Vec::new();
+  data.write_binary(&mut buf).unwrap();
+
+  let loaded = TrainingData::from_binary(&mut buf.as_slice()).unwrap();
+
+  assert!(loaded.contains_abbrev("dr"));
+  assert!(loaded.contains_abbrev("inc"));
+  assert!(loaded.contains_sentence_starter("the"));
+  assert!(loaded.contains_collocation("new", "york"));
+  assert_eq!(loaded.get_orthographic_context("the"), Some(&0x02u8));
+}
+
+#[test]
+fn test_json_round_trip_test() {
+  let mut data: TrainingData = Default::default();
+
+  data.insert_abbrev("dr");
+  data.insert_sentence_starter("the");
+  data.insert_collocation("new", "york");
+  data.insert_orthographic_context("the", 0x02);
+
+  let encoded = data.to_string();
+  let loaded: TrainingData = FromStr::from_str(encoded.as_slice()).unwrap();
+
+  assert!(loaded.contains_abbrev("dr"));
+  assert!(loaded.contains_sentence_starter("the"));
+  assert!(loaded.contains_collocation("new", "york"));
+  assert_eq!(loaded.get_orthographic_context("the"), Some(&0x02u8));
+}
+
+#[test]
+fn test_extend_unions_and_ors_context_test() {
+  let mut base: TrainingData = Default::default();
+  base.insert_abbrev("dr");
+  base.insert_orthographic_context("the", 0x01);
+
+  let mut other: TrainingData = Default::default();
+  other.insert_abbrev("inc");
+  other.insert_sentence_starter("however");
+  other.insert_collocation("new", "york");
+  other.insert_orthographic_context("the", 0x02);
+
+  let merged = base.merge(other);
+
+  assert!(merged.contains_abbrev("dr"));
+  assert!(merged.contains_abbrev("inc"));
+  assert!(merged.contains_sentence_starter("however"));
+  assert!(merged.contains_collocation("new", "york"));
+
+  // Context flags for a shared key are OR-ed together, not overwritten.
+  assert_eq!(merged.get_orthographic_context("the"), Some(&0x03u8));
+}
+
+#[test]
+fn test_parse_wordlist_entry_test() {
+  // Trailing period and Hunspell affix suffix are stripped, case is folded.
+  assert_eq!(parse_wordlist_entry("Dr."), Some("dr".to_string()));
+  assert_eq!(parse_wordlist_entry("Inc./MS"), Some("inc".to_string()));
+  assert_eq!(parse_wordlist_entry("  vs.  "), Some("vs".to_string()));
+
+  // Blank lines and comments carry no token.
+  assert_eq!(parse_wordlist_entry(""), None);
+  assert_eq!(parse_wordlist_entry("   "), None);
+  assert_eq!(parse_wordlist_entry("# comment"), None);
+}
+
+#[test]
+fn test_load_abbrevs_from_reader_test() {
+  let mut data: TrainingData = Default::default();
+  let mut src = b"Dr.\n# a comment\n\nvs./MS\n".as_slice();
+
+  data.load_abbrevs_from_reader(&mut src);
+
+  assert!(data.contains_abbrev("dr"));
+  assert!(data.contains_abbrev("vs"));
+  assert_eq!(data.abbrevs_len(), 2);
+}